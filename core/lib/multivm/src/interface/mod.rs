@@ -1,7 +1,10 @@
 pub(crate) mod traits;
 
 pub use traits::tracers::{dyn_tracers, multivm_tracer::MultivmTracer};
-pub use traits::vm::{VmInterface, VmInterfaceHistoryEnabled};
+pub use traits::vm::{
+    FrameDepth, SnapshotId, VmInterface, VmInterfaceHistoryEnabled, WorldDiff, WorldEvent,
+    WorldStorageChange, WorldTransientStorageChange,
+};
 pub mod types;
 
 pub use types::{
@@ -57,9 +57,54 @@ use crate::interface::{FinishedL1Batch, VmMemoryMetrics};
 use crate::vm_latest::HistoryEnabled;
 use crate::HistoryMode;
 use zksync_state::StoragePtr;
-use zksync_types::Transaction;
+use zksync_types::{StorageKey, Transaction, VmEvent, H256, U256};
 use zksync_utils::bytecode::CompressedBytecodeInfo;
 
+/// Call-frame depth (0 = top level) at which a piece of rollbackable world state was touched.
+pub type FrameDepth = usize;
+
+/// A pending write to a single contract storage slot, not yet committed to the batch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WorldStorageChange {
+    pub key: StorageKey,
+    pub value: H256,
+    pub depth: FrameDepth,
+}
+
+/// An event (L2->L1 log) appended by VM execution, not yet committed to the batch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorldEvent {
+    pub event: VmEvent,
+    pub depth: FrameDepth,
+}
+
+/// A pending write to a single transient storage slot (EIP-1153), not yet committed to the
+/// batch. Unlike regular storage, transient writes are always reverted at the end of the
+/// transaction, and also revert if the frame that made them panics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WorldTransientStorageChange {
+    pub key: StorageKey,
+    pub value: U256,
+    pub depth: FrameDepth,
+}
+
+/// Snapshot of the rollbackable `World` portion of VM state accumulated since the start of
+/// the batch (or since a given [`SnapshotId`]): pending storage writes, appended events,
+/// transient storage writes, and newly decommitted bytecode. This is deliberately narrower
+/// than [`CurrentExecutionState`], which also carries the transient `Execution` state
+/// (current frame, gas, refunds) that a rollback wouldn't undo.
+///
+/// The current `VmInterface` implementations fill in only `storage_changes` and `events`,
+/// and tag every entry with depth 0; `transient_storage_changes` and
+/// `decommitted_bytecodes` are always empty.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WorldDiff {
+    pub storage_changes: Vec<WorldStorageChange>,
+    pub events: Vec<WorldEvent>,
+    pub transient_storage_changes: Vec<WorldTransientStorageChange>,
+    pub decommitted_bytecodes: Vec<H256>,
+}
+
 /// Public interface for VM
 pub trait VmInterface<S, H: HistoryMode> {
     type TracerDispatcher: Default;
@@ -95,6 +140,11 @@ pub trait VmInterface<S, H: HistoryMode> {
     /// Get the current state of the virtual machine.
     fn get_current_execution_state(&self) -> CurrentExecutionState;
 
+    /// Read the current value of a transient storage (EIP-1153) slot. Transient slots are
+    /// implicitly zero-initialized and are rolled back both at the end of the transaction
+    /// and when the frame that wrote them panics.
+    fn read_transient_storage(&self, key: StorageKey) -> U256;
+
     /// Execute transaction with optional bytecode compression.
     fn execute_transaction_with_bytecode_compression(
         &mut self,
@@ -116,18 +166,36 @@ pub trait VmInterface<S, H: HistoryMode> {
         with_compression: bool,
     ) -> Result<VmExecutionResultAndLogs, BytecodeCompressionError>;
 
+    /// Peek at the rollbackable `World` state (pending storage writes, appended events, and
+    /// newly decommitted bytecode) accumulated since `since`, without taking a snapshot.
+    /// `since: None` means "since the start of the batch". See [`WorldDiff`] for which parts
+    /// the current implementations fill in.
+    fn peek_world_diff(&self, since: Option<SnapshotId>) -> WorldDiff;
+
     fn record_vm_memory_metrics(&self) -> VmMemoryMetrics;
     fn finish_batch(&mut self) -> FinishedL1Batch;
 }
 
+/// Handle to a VM snapshot created via [`VmInterfaceHistoryEnabled::make_snapshot`].
+///
+/// Unlike matching every `make_snapshot` call with exactly one rollback/pop in LIFO order,
+/// an id stays valid until it, or any snapshot taken before it, is rolled back to or popped,
+/// so callers can keep several nested "try this, maybe undo" scopes open at once and resolve
+/// an outer scope without first resolving the inner ones (which it then discards).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SnapshotId(pub(crate) usize);
+
 /// Methods of vm, which required some history manipullations
 pub trait VmInterfaceHistoryEnabled<S>: VmInterface<S, HistoryEnabled> {
-    /// Create snapshot of current vm state and push it into the memory
-    fn make_snapshot(&mut self);
+    /// Create a snapshot of the current vm state and push it onto the snapshot stack,
+    /// returning a handle that can later be used to roll back to (or discard) this point.
+    fn make_snapshot(&mut self) -> SnapshotId;
 
-    /// Roll back VM state to the latest snapshot and destroy the snapshot.
-    fn rollback_to_the_latest_snapshot(&mut self);
+    /// Roll back VM state to the given snapshot, destroying it along with any snapshots
+    /// taken after it.
+    fn rollback_to_snapshot(&mut self, snapshot_id: SnapshotId);
 
-    /// Pop the latest snapshot from memory and destroy it.
-    fn pop_snapshot_no_rollback(&mut self);
+    /// Discard the given snapshot, along with any snapshots taken after it, without
+    /// rolling back.
+    fn pop_snapshot(&mut self, snapshot_id: SnapshotId);
 }
\ No newline at end of file
@@ -0,0 +1,101 @@
+use zk_evm_1_3_3::{
+    tracing::{BeforeExecutionData, VmLocalStateData},
+    zkevm_opcode_defs::{FarCallOpcode, Opcode},
+};
+use zksync_state::{StoragePtr, WriteStorage};
+
+use super::{
+    frame_events, frame_storage_writes, ret_is_rollback, FrameCursor, FrameOutcome, FrameRollback,
+    RollbackTracer,
+};
+use crate::interface::dyn_tracers::vm_1_3_3::DynTracer;
+use crate::interface::tracer::VmExecutionStopReason;
+use crate::interface::VmExecutionResultAndLogs;
+use crate::vm_latest::{
+    BootloaderState, ExecutionEndTracer, ExecutionProcessing, HistoryMode, SimpleMemory, VmTracer,
+    ZkSyncVmState,
+};
+
+impl<S: WriteStorage, H: HistoryMode> DynTracer<S, SimpleMemory<H>> for RollbackTracer {
+    fn before_execution(
+        &mut self,
+        _state: VmLocalStateData<'_>,
+        data: BeforeExecutionData,
+        _memory: &SimpleMemory<H>,
+        _storage: StoragePtr<S>,
+    ) {
+        match data.opcode.variant.opcode {
+            Opcode::FarCall(FarCallOpcode::Normal)
+            | Opcode::FarCall(FarCallOpcode::Delegate)
+            | Opcode::FarCall(FarCallOpcode::Mimic)
+            | Opcode::NearCall(_) => {
+                self.pending_frame_entry = true;
+            }
+            Opcode::Ret(variant) => {
+                self.pending_frame_exit = Some(ret_is_rollback(variant));
+            }
+            _ => {}
+        }
+    }
+}
+
+impl<H: HistoryMode> ExecutionEndTracer<H> for RollbackTracer {
+    fn should_stop_execution(&self) -> bool {
+        false
+    }
+}
+
+impl<S: WriteStorage, H: HistoryMode> ExecutionProcessing<S, H> for RollbackTracer {
+    fn after_cycle(
+        &mut self,
+        state: &mut ZkSyncVmState<S, H>,
+        _bootloader_state: &mut BootloaderState,
+    ) {
+        if self.pending_frame_entry {
+            self.pending_frame_entry = false;
+            self.stack.push(FrameCursor {
+                storage_writes_before: state.storage.get_history_size(),
+                events_before: state.event_sink.get_history_size(),
+            });
+        }
+
+        if let Some(rolled_back) = self.pending_frame_exit.take() {
+            if let Some(cursor) = self.stack.pop() {
+                let storage_history: Vec<_> = state
+                    .storage
+                    .get_history()
+                    .iter()
+                    .map(|entry| (entry.key, entry.value))
+                    .collect();
+                let storage_writes = frame_storage_writes(&storage_history, cursor.storage_writes_before);
+                let events_history: Vec<_> = state.event_sink.get_history().to_vec();
+                let events = frame_events(&events_history, cursor.events_before);
+                self.frames.push(FrameRollback {
+                    storage_writes,
+                    events,
+                    transient_storage_writes: Vec::new(),
+                    outcome: if rolled_back {
+                        FrameOutcome::RolledBack
+                    } else {
+                        FrameOutcome::Committed
+                    },
+                });
+            }
+        }
+    }
+
+    fn after_vm_execution(
+        &mut self,
+        _state: &mut ZkSyncVmState<S, H>,
+        _bootloader_state: &BootloaderState,
+        _stop_reason: VmExecutionStopReason,
+    ) {
+        self.store_result();
+    }
+}
+
+impl<S: WriteStorage, H: HistoryMode> VmTracer<S, H> for RollbackTracer {
+    fn save_results(&mut self, _result: &mut VmExecutionResultAndLogs) {
+        self.store_result();
+    }
+}
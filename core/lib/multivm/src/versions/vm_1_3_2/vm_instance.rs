@@ -1,7 +1,7 @@
 use crate::interface::{
     BootloaderMemory, BytecodeCompressionError, CurrentExecutionState, FinishedL1Batch, L1BatchEnv,
-    L2BlockEnv, SystemEnv, TxExecutionMode, VmExecutionMode, VmExecutionResultAndLogs, VmInterface,
-    VmInterfaceHistoryEnabled, VmMemoryMetrics,
+    L2BlockEnv, SnapshotId, SystemEnv, TxExecutionMode, VmExecutionMode, VmExecutionResultAndLogs,
+    VmInterface, VmInterfaceHistoryEnabled, VmMemoryMetrics,
 };
 use std::any::Any;
 
@@ -21,6 +21,14 @@ pub struct Vm<S: WriteStorage, H: HistoryMode> {
     pub(crate) vm: VmInstance<S, H::Vm1_3_2Mode>,
     pub(crate) system_env: SystemEnv,
     pub(crate) last_tx_compressed_bytecodes: Vec<CompressedBytecodeInfo>,
+    /// Number of snapshots currently pushed onto the inner vm's LIFO snapshot stack.
+    /// Used to emulate [`SnapshotId`]-addressed rollback/pop on top of a VM that only
+    /// natively supports rolling back to (or popping) its latest snapshot.
+    pub(crate) snapshots_count: usize,
+    /// Storage/event history length at the time each live snapshot was taken, indexed by
+    /// `SnapshotId`. Lets `peek_world_diff` answer "since a given snapshot" without the
+    /// inner vm needing to know about `SnapshotId` at all.
+    pub(crate) snapshot_world_cursors: Vec<(usize, usize)>,
 }
 
 impl<S: WriteStorage, H: HistoryMode> VmInterface<S, H> for Vm<S, H> {
@@ -48,6 +56,8 @@ impl<S: WriteStorage, H: HistoryMode> VmInterface<S, H> for Vm<S, H> {
             vm: inner_vm,
             system_env,
             last_tx_compressed_bytecodes: vec![],
+            snapshots_count: 0,
+            snapshot_world_cursors: vec![],
         }
     }
 
@@ -106,6 +116,11 @@ impl<S: WriteStorage, H: HistoryMode> VmInterface<S, H> for Vm<S, H> {
         panic!("Not supported for vm before vm with virtual blocks, use `finish_batch` instead")
     }
 
+    fn read_transient_storage(&self, _key: zksync_types::StorageKey) -> zksync_types::U256 {
+        // Vm 1.3.2 predates EIP-1153 transient storage, so every slot reads as zero.
+        zksync_types::U256::zero()
+    }
+
     fn inspect_transaction_with_bytecode_compression(
         &mut self,
         _tracer: Self::TracerDispatcher,
@@ -177,6 +192,47 @@ impl<S: WriteStorage, H: HistoryMode> VmInterface<S, H> for Vm<S, H> {
         }
     }
 
+    fn peek_world_diff(&self, since: Option<SnapshotId>) -> crate::interface::WorldDiff {
+        let (storage_since, events_since) =
+            world_cursor_since(&self.snapshot_world_cursors, since);
+
+        // Vm 1.3.2 predates call-frame depth tracking, so every change is attributed to the
+        // top-level frame (depth 0); it also has no queryable decommitted-bytecode log, so
+        // that part of the diff is always empty here.
+        let storage_changes = self
+            .vm
+            .state
+            .storage
+            .get_history()
+            .iter()
+            .skip(storage_since)
+            .map(|entry| crate::interface::WorldStorageChange {
+                key: entry.key,
+                value: entry.value,
+                depth: 0,
+            })
+            .collect();
+        let events = self
+            .vm
+            .state
+            .event_sink
+            .get_history()
+            .iter()
+            .skip(events_since)
+            .map(|event| crate::interface::WorldEvent {
+                event: event.clone(),
+                depth: 0,
+            })
+            .collect();
+
+        crate::interface::WorldDiff {
+            storage_changes,
+            events,
+            transient_storage_changes: vec![],
+            decommitted_bytecodes: vec![],
+        }
+    }
+
     fn record_vm_memory_metrics(&self) -> VmMemoryMetrics {
         VmMemoryMetrics {
             event_sink_inner: self.vm.state.event_sink.get_size(),
@@ -204,15 +260,120 @@ impl<S: WriteStorage, H: HistoryMode> VmInterface<S, H> for Vm<S, H> {
 }
 
 impl<S: WriteStorage> VmInterfaceHistoryEnabled<S> for Vm<S, crate::vm_latest::HistoryEnabled> {
-    fn make_snapshot(&mut self) {
-        self.vm.save_current_vm_as_snapshot()
+    fn make_snapshot(&mut self) -> SnapshotId {
+        self.vm.save_current_vm_as_snapshot();
+        let id = SnapshotId(self.snapshots_count);
+        self.snapshot_world_cursors.push((
+            self.vm.state.storage.get_history_size(),
+            self.vm.state.event_sink.get_history_size(),
+        ));
+        self.snapshots_count += 1;
+        id
     }
 
-    fn rollback_to_the_latest_snapshot(&mut self) {
+    fn rollback_to_snapshot(&mut self, snapshot_id: SnapshotId) {
+        // Vm 1.3.2 only exposes a LIFO "latest snapshot" primitive, so rolling back to an
+        // arbitrary earlier snapshot means first discarding every snapshot taken after it.
+        let discards = snapshots_to_discard(self.snapshots_count, snapshot_id);
+        for _ in 0..discards {
+            self.vm.pop_snapshot_no_rollback();
+        }
         self.vm.rollback_to_latest_snapshot_popping();
+        self.snapshots_count = snapshot_id.0;
+        self.snapshot_world_cursors.truncate(snapshot_id.0);
+    }
+
+    fn pop_snapshot(&mut self, snapshot_id: SnapshotId) {
+        let discards = snapshots_to_discard(self.snapshots_count, snapshot_id);
+        for _ in 0..discards {
+            self.vm.pop_snapshot_no_rollback();
+        }
+        self.vm.pop_snapshot_no_rollback();
+        self.snapshots_count = snapshot_id.0;
+        self.snapshot_world_cursors.truncate(snapshot_id.0);
+    }
+}
+
+/// Number of snapshots that must be discarded, via repeated `pop_snapshot_no_rollback`,
+/// above `snapshot_id` before the inner vm's latest snapshot is `snapshot_id` itself.
+///
+/// Panics if `snapshot_id` isn't currently live, i.e. it was never taken, or has already
+/// been rolled back/popped — reusing it would otherwise either no-op against the wrong
+/// (unrelated) snapshot or desync `snapshots_count` from the inner vm's real stack depth.
+fn snapshots_to_discard(snapshots_count: usize, snapshot_id: SnapshotId) -> usize {
+    assert!(
+        snapshot_id.0 < snapshots_count,
+        "snapshot_id {snapshot_id:?} is unknown: it was never taken, or has already been \
+         rolled back/popped"
+    );
+    snapshots_count - snapshot_id.0 - 1
+}
+
+/// Resolves `since` to a `(storage_history_len, events_history_len)` cursor to skip up to in
+/// `peek_world_diff`, the same way `snapshots_to_discard` resolves a `SnapshotId` for rollback:
+/// an unknown `since` (never taken, or already rolled back/popped) panics rather than silently
+/// defaulting to "since batch start", which would otherwise misreport an arbitrary diff as if
+/// the caller's requested starting point were still live.
+fn world_cursor_since(
+    cursors: &[(usize, usize)],
+    since: Option<SnapshotId>,
+) -> (usize, usize) {
+    since
+        .map(|id| {
+            cursors.get(id.0).copied().unwrap_or_else(|| {
+                panic!(
+                    "snapshot_id {id:?} is unknown: it was never taken, or has already been \
+                     rolled back/popped"
+                )
+            })
+        })
+        .unwrap_or((0, 0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_discards_needed_for_the_latest_snapshot() {
+        assert_eq!(snapshots_to_discard(3, SnapshotId(2)), 0);
+    }
+
+    #[test]
+    fn discards_every_snapshot_above_the_target() {
+        assert_eq!(snapshots_to_discard(3, SnapshotId(0)), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "snapshot_id")]
+    fn rejects_a_snapshot_id_reused_after_it_was_already_resolved() {
+        // Simulates rolling back to snapshot 1, then mistakenly reusing the same id again.
+        let snapshots_count_after_rollback = SnapshotId(1).0;
+        snapshots_to_discard(snapshots_count_after_rollback, SnapshotId(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "snapshot_id")]
+    fn rejects_a_snapshot_id_from_the_future() {
+        snapshots_to_discard(2, SnapshotId(5));
+    }
+
+    #[test]
+    fn world_cursor_since_batch_start_is_zero() {
+        assert_eq!(world_cursor_since(&[(3, 1), (5, 2)], None), (0, 0));
+    }
+
+    #[test]
+    fn world_cursor_since_a_live_snapshot_is_its_recorded_cursor() {
+        assert_eq!(
+            world_cursor_since(&[(3, 1), (5, 2)], Some(SnapshotId(1))),
+            (5, 2)
+        );
     }
 
-    fn pop_snapshot_no_rollback(&mut self) {
-        self.vm.pop_snapshot_no_rollback()
+    #[test]
+    #[should_panic(expected = "snapshot_id")]
+    fn world_cursor_since_rejects_a_stale_or_unknown_snapshot_id() {
+        world_cursor_since(&[(3, 1)], Some(SnapshotId(5)));
     }
 }
\ No newline at end of file
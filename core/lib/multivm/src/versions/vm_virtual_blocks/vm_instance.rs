@@ -0,0 +1,239 @@
+use crate::glue::history_mode::HistoryMode;
+use crate::interface::{
+    BootloaderMemory, BytecodeCompressionError, CurrentExecutionState, FinishedL1Batch, L1BatchEnv,
+    L2BlockEnv, SnapshotId, SystemEnv, VmExecutionMode, VmExecutionResultAndLogs, VmInterface,
+    VmInterfaceHistoryEnabled, VmMemoryMetrics,
+};
+use crate::tracers::multivm_dispatcher::TracerDispatcher;
+use zksync_state::{StoragePtr, WriteStorage};
+use zksync_types::{StorageKey, Transaction, U256};
+use zksync_utils::bytecode::CompressedBytecodeInfo;
+
+#[derive(Debug)]
+pub struct Vm<S: WriteStorage, H: HistoryMode> {
+    pub(crate) vm: crate::vm_virtual_blocks::Vm<S, H::VmVirtualBlocksMode>,
+    /// Number of snapshots currently pushed onto the inner vm's LIFO snapshot stack.
+    /// Used to emulate [`SnapshotId`]-addressed rollback/pop on top of a VM that only
+    /// natively supports rolling back to (or popping) its latest snapshot.
+    pub(crate) snapshots_count: usize,
+    /// Storage/event history length at the time each live snapshot was taken, indexed by
+    /// `SnapshotId`. Lets `peek_world_diff` answer "since a given snapshot" without the
+    /// inner vm needing to know about `SnapshotId` at all.
+    pub(crate) snapshot_world_cursors: Vec<(usize, usize)>,
+}
+
+impl<S: WriteStorage, H: HistoryMode> VmInterface<S, H> for Vm<S, H> {
+    type TracerDispatcher = TracerDispatcher<S, H>;
+
+    fn new(batch_env: L1BatchEnv, system_env: SystemEnv, storage: StoragePtr<S>) -> Self {
+        Self {
+            vm: crate::vm_virtual_blocks::Vm::new(batch_env, system_env, storage),
+            snapshots_count: 0,
+            snapshot_world_cursors: vec![],
+        }
+    }
+
+    fn push_transaction(&mut self, tx: Transaction) {
+        self.vm.push_transaction(tx)
+    }
+
+    fn inspect(
+        &mut self,
+        dispatcher: Self::TracerDispatcher,
+        execution_mode: VmExecutionMode,
+    ) -> VmExecutionResultAndLogs {
+        self.vm.inspect(dispatcher.into(), execution_mode)
+    }
+
+    fn get_bootloader_memory(&self) -> BootloaderMemory {
+        self.vm.get_bootloader_memory()
+    }
+
+    fn get_last_tx_compressed_bytecodes(&self) -> Vec<CompressedBytecodeInfo> {
+        self.vm.get_last_tx_compressed_bytecodes()
+    }
+
+    fn start_new_l2_block(&mut self, l2_block_env: L2BlockEnv) {
+        self.vm.start_new_l2_block(l2_block_env)
+    }
+
+    fn get_current_execution_state(&self) -> CurrentExecutionState {
+        self.vm.get_current_execution_state()
+    }
+
+    fn read_transient_storage(&self, key: StorageKey) -> U256 {
+        self.vm.read_transient_storage(key)
+    }
+
+    fn inspect_transaction_with_bytecode_compression(
+        &mut self,
+        tracer: Self::TracerDispatcher,
+        tx: Transaction,
+        with_compression: bool,
+    ) -> Result<VmExecutionResultAndLogs, BytecodeCompressionError> {
+        self.vm
+            .inspect_transaction_with_bytecode_compression(tracer.into(), tx, with_compression)
+    }
+
+    fn peek_world_diff(&self, since: Option<SnapshotId>) -> crate::interface::WorldDiff {
+        let (storage_since, events_since) = world_cursor_since(&self.snapshot_world_cursors, since);
+
+        // Like vm_1_3_2, this glue layer doesn't yet track call-frame depth, so every change
+        // is attributed to the top-level frame (depth 0); it also has no queryable
+        // decommitted-bytecode log, so that part of the diff is always empty here.
+        let storage_changes = self
+            .vm
+            .state
+            .storage
+            .get_history()
+            .iter()
+            .skip(storage_since)
+            .map(|entry| crate::interface::WorldStorageChange {
+                key: entry.key,
+                value: entry.value,
+                depth: 0,
+            })
+            .collect();
+        let events = self
+            .vm
+            .state
+            .event_sink
+            .get_history()
+            .iter()
+            .skip(events_since)
+            .map(|event| crate::interface::WorldEvent {
+                event: event.clone(),
+                depth: 0,
+            })
+            .collect();
+
+        crate::interface::WorldDiff {
+            storage_changes,
+            events,
+            transient_storage_changes: vec![],
+            decommitted_bytecodes: vec![],
+        }
+    }
+
+    fn record_vm_memory_metrics(&self) -> VmMemoryMetrics {
+        self.vm.record_vm_memory_metrics()
+    }
+
+    fn finish_batch(&mut self) -> FinishedL1Batch {
+        self.vm.finish_batch()
+    }
+}
+
+impl<S: WriteStorage> VmInterfaceHistoryEnabled<S> for Vm<S, crate::vm_latest::HistoryEnabled> {
+    fn make_snapshot(&mut self) -> SnapshotId {
+        self.vm.make_snapshot();
+        let id = SnapshotId(self.snapshots_count);
+        self.snapshot_world_cursors.push((
+            self.vm.state.storage.get_history_size(),
+            self.vm.state.event_sink.get_history_size(),
+        ));
+        self.snapshots_count += 1;
+        id
+    }
+
+    fn rollback_to_snapshot(&mut self, snapshot_id: SnapshotId) {
+        // Like vm_1_3_2, this glue layer only assumes the inner vm exposes a LIFO "latest
+        // snapshot" primitive, so rolling back to an arbitrary earlier snapshot means first
+        // discarding every snapshot taken after it.
+        let discards = snapshots_to_discard(self.snapshots_count, snapshot_id);
+        for _ in 0..discards {
+            self.vm.pop_snapshot_no_rollback();
+        }
+        self.vm.rollback_to_the_latest_snapshot();
+        self.snapshots_count = snapshot_id.0;
+        self.snapshot_world_cursors.truncate(snapshot_id.0);
+    }
+
+    fn pop_snapshot(&mut self, snapshot_id: SnapshotId) {
+        let discards = snapshots_to_discard(self.snapshots_count, snapshot_id);
+        for _ in 0..discards {
+            self.vm.pop_snapshot_no_rollback();
+        }
+        self.vm.pop_snapshot_no_rollback();
+        self.snapshots_count = snapshot_id.0;
+        self.snapshot_world_cursors.truncate(snapshot_id.0);
+    }
+}
+
+/// Number of snapshots that must be discarded, via repeated `pop_snapshot_no_rollback`,
+/// above `snapshot_id` before the inner vm's latest snapshot is `snapshot_id` itself.
+///
+/// Panics if `snapshot_id` isn't currently live, i.e. it was never taken, or has already
+/// been rolled back/popped — reusing it would otherwise either no-op against the wrong
+/// (unrelated) snapshot or desync `snapshots_count` from the inner vm's real stack depth.
+fn snapshots_to_discard(snapshots_count: usize, snapshot_id: SnapshotId) -> usize {
+    assert!(
+        snapshot_id.0 < snapshots_count,
+        "snapshot_id {snapshot_id:?} is unknown: it was never taken, or has already been \
+         rolled back/popped"
+    );
+    snapshots_count - snapshot_id.0 - 1
+}
+
+/// Resolves `since` to a `(storage_history_len, events_history_len)` cursor to skip up to in
+/// `peek_world_diff`; an unknown `since` (never taken, or already rolled back/popped) panics
+/// rather than silently defaulting to "since batch start".
+fn world_cursor_since(cursors: &[(usize, usize)], since: Option<SnapshotId>) -> (usize, usize) {
+    since
+        .map(|id| {
+            cursors.get(id.0).copied().unwrap_or_else(|| {
+                panic!(
+                    "snapshot_id {id:?} is unknown: it was never taken, or has already been \
+                     rolled back/popped"
+                )
+            })
+        })
+        .unwrap_or((0, 0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_discards_needed_for_the_latest_snapshot() {
+        assert_eq!(snapshots_to_discard(3, SnapshotId(2)), 0);
+    }
+
+    #[test]
+    fn discards_every_snapshot_above_the_target() {
+        assert_eq!(snapshots_to_discard(3, SnapshotId(0)), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "snapshot_id")]
+    fn rejects_a_snapshot_id_reused_after_it_was_already_resolved() {
+        let snapshots_count_after_rollback = SnapshotId(1).0;
+        snapshots_to_discard(snapshots_count_after_rollback, SnapshotId(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "snapshot_id")]
+    fn rejects_a_snapshot_id_from_the_future() {
+        snapshots_to_discard(2, SnapshotId(5));
+    }
+
+    #[test]
+    fn world_cursor_since_batch_start_is_zero() {
+        assert_eq!(world_cursor_since(&[(3, 1), (5, 2)], None), (0, 0));
+    }
+
+    #[test]
+    fn world_cursor_since_a_live_snapshot_is_its_recorded_cursor() {
+        assert_eq!(
+            world_cursor_since(&[(3, 1), (5, 2)], Some(SnapshotId(1))),
+            (5, 2)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "snapshot_id")]
+    fn world_cursor_since_rejects_a_stale_or_unknown_snapshot_id() {
+        world_cursor_since(&[(3, 1)], Some(SnapshotId(5)));
+    }
+}